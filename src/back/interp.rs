@@ -0,0 +1,502 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::ast::{BinaryOp, Comparison, CoordinateAccess, UnaryOp};
+use crate::compile::ir::{Id, IROp, IRInstructionSeq, IRType};
+
+/// A concrete value produced by evaluating IR, one variant per value-carrying [`IRType`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Vec2(f64, f64),
+    Vec3(f64, f64, f64),
+    NumberList(Vec<f64>),
+    Vec2List(Vec<(f64, f64)>),
+    Vec3List(Vec<(f64, f64, f64)>),
+}
+
+impl Value {
+    fn type_of(&self) -> IRType {
+        match self {
+            Value::Number(_) => IRType::Number,
+            Value::Vec2(..) => IRType::Vec2,
+            Value::Vec3(..) => IRType::Vec3,
+            Value::NumberList(_) => IRType::NumberList,
+            Value::Vec2List(_) => IRType::Vec2List,
+            Value::Vec3List(_) => IRType::Vec3List,
+        }
+    }
+    fn as_number(&self) -> Result<f64, InterpError> {
+        match self {
+            Value::Number(n) => Ok(*n),
+            other => Err(InterpError::TypeMismatch {
+                expected: IRType::Number,
+                found: other.type_of(),
+            }),
+        }
+    }
+}
+
+/// Errors produced while tree-walking an [`IRInstructionSeq`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterpError {
+    /// An operand or argument had a different type than the operator expected.
+    TypeMismatch { expected: IRType, found: IRType },
+    /// `LoadArg` referenced an index past the end of the args slice.
+    MissingArg(u32),
+    /// An `Id` referenced an instruction that hasn't produced a value yet.
+    UnboundId(Id),
+    /// A `BeginBroadcast` had no matching `EndBroadcast` pointing back at it, or an
+    /// `EndBroadcast` appeared outside the broadcast it closes.
+    UnmatchedBroadcast(Id),
+    /// A `BeginPiecewise` had no matching `EndPiecewise` at the same nesting depth, or an
+    /// `InnerPiecewise`/`EndPiecewise` appeared outside an open piecewise.
+    UnmatchedPiecewise(Id),
+    /// `SetBroadcastArg`/`LoadBroadcastArg` appeared outside an open broadcast scope.
+    BroadcastArgOutsideLoop(Id),
+    /// `SetBroadcastArg` indexed past the end of its source list.
+    IndexOutOfRange(usize),
+    /// A broadcast's `end_index` evaluated to a negative count.
+    NegativeBroadcastLength(i64),
+    /// The sequence ran to completion without executing a `Ret`.
+    NoReturn,
+    /// An operator this interpreter doesn't implement yet.
+    UnsupportedOp(String),
+}
+
+impl fmt::Display for InterpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterpError::TypeMismatch { expected, found } => {
+                write!(f, "type mismatch: expected {expected:?}, found {found:?}")
+            }
+            InterpError::MissingArg(i) => write!(f, "missing argument at index {i}"),
+            InterpError::UnboundId(id) => write!(f, "referenced unbound id {}", id.index()),
+            InterpError::UnmatchedBroadcast(id) => {
+                write!(f, "broadcast instruction at {} is unmatched", id.index())
+            }
+            InterpError::UnmatchedPiecewise(id) => {
+                write!(f, "piecewise instruction at {} is unmatched", id.index())
+            }
+            InterpError::BroadcastArgOutsideLoop(id) => write!(
+                f,
+                "broadcast arg instruction at {} appeared outside an open broadcast scope",
+                id.index()
+            ),
+            InterpError::IndexOutOfRange(i) => write!(f, "broadcast index {i} out of range"),
+            InterpError::NegativeBroadcastLength(n) => {
+                write!(f, "broadcast end_index evaluated to negative count {n}")
+            }
+            InterpError::NoReturn => write!(f, "sequence executed without hitting a Ret"),
+            InterpError::UnsupportedOp(op) => write!(f, "unsupported operator: {op}"),
+        }
+    }
+}
+impl std::error::Error for InterpError {}
+
+struct State<'a> {
+    args: &'a [Value],
+    regs: BTreeMap<Id, Value>,
+    bools: BTreeMap<Id, bool>,
+    broadcast_args: Vec<BTreeMap<u8, Value>>,
+}
+
+impl State<'_> {
+    fn get(&self, id: Id) -> Result<Value, InterpError> {
+        self.regs.get(&id).cloned().ok_or(InterpError::UnboundId(id))
+    }
+    fn num(&self, id: Id) -> Result<f64, InterpError> {
+        self.get(id)?.as_number()
+    }
+}
+
+/// Evaluates `seq` against concrete `args`, returning the value of its `Ret`.
+///
+/// This is a tree-walking interpreter over the flat IR, meant for unit-testing the
+/// frontend/lowering against expected numeric output without going through a real
+/// codegen target.
+pub fn interpret(seq: &IRInstructionSeq, args: &[Value]) -> Result<Value, InterpError> {
+    let ops: Vec<(Id, IROp)> = seq.iter().map(|(id, op)| (*id, *op)).collect();
+    let mut state = State {
+        args,
+        regs: BTreeMap::new(),
+        bools: BTreeMap::new(),
+        broadcast_args: Vec::new(),
+    };
+    run(&ops, &mut state, None)?.ok_or(InterpError::NoReturn)
+}
+
+/// Runs a (possibly nested, e.g. a broadcast body) slice of instructions in `Id` order.
+/// `iter_idx` is the current broadcast loop iteration, used only by `SetBroadcastArg`.
+fn run(
+    ops: &[(Id, IROp)],
+    state: &mut State,
+    iter_idx: Option<usize>,
+) -> Result<Option<Value>, InterpError> {
+    let mut i = 0;
+    while i < ops.len() {
+        let (id, op) = ops[i];
+        match op {
+            IROp::BeginBroadcast {
+                end_index,
+                write_to,
+            } => {
+                let end = ops[i + 1..]
+                    .iter()
+                    .position(|(_, o)| matches!(o, IROp::EndBroadcast { begin, .. } if *begin == id))
+                    .map(|p| p + i + 1)
+                    .ok_or(InterpError::UnmatchedBroadcast(id))?;
+                let ret = match ops[end].1 {
+                    IROp::EndBroadcast { ret, .. } => ret,
+                    _ => unreachable!("position() matched an EndBroadcast above"),
+                };
+                let body = &ops[i + 1..end];
+
+                let count = state.num(end_index)? as i64 + 1;
+                if count < 0 {
+                    return Err(InterpError::NegativeBroadcastLength(count));
+                }
+                let mut numbers = Vec::new();
+                let mut vec2s = Vec::new();
+                let mut vec3s = Vec::new();
+                for n in 0..count as usize {
+                    state.broadcast_args.push(BTreeMap::new());
+                    let result = run(body, state, Some(n));
+                    state.broadcast_args.pop();
+                    result?;
+                    match state.get(ret)? {
+                        Value::Number(x) => numbers.push(x),
+                        Value::Vec2(x, y) => vec2s.push((x, y)),
+                        Value::Vec3(x, y, z) => vec3s.push((x, y, z)),
+                        other => {
+                            return Err(InterpError::TypeMismatch {
+                                expected: IRType::Number,
+                                found: other.type_of(),
+                            })
+                        }
+                    }
+                }
+                let list = match write_to.t {
+                    IRType::NumberList => Value::NumberList(numbers),
+                    IRType::Vec2List => Value::Vec2List(vec2s),
+                    IRType::Vec3List => Value::Vec3List(vec3s),
+                    t => {
+                        return Err(InterpError::TypeMismatch {
+                            expected: IRType::NumberList,
+                            found: t,
+                        })
+                    }
+                };
+                state.regs.insert(write_to, list);
+                i = end + 1;
+                continue;
+            }
+            IROp::BeginPiecewise { comp, res } => {
+                let mut arms = vec![(comp, res)];
+                let mut depth = 0usize;
+                let mut end = i + 1;
+                loop {
+                    match ops.get(end).map(|(_, o)| o) {
+                        Some(IROp::BeginPiecewise { .. }) => depth += 1,
+                        Some(IROp::InnerPiecewise { comp, res }) if depth == 0 => {
+                            arms.push((*comp, *res));
+                        }
+                        Some(IROp::EndPiecewise { .. }) if depth == 0 => break,
+                        Some(IROp::EndPiecewise { .. }) => depth -= 1,
+                        Some(_) => {}
+                        None => return Err(InterpError::UnmatchedPiecewise(id)),
+                    }
+                    end += 1;
+                }
+                let default = match ops[end].1 {
+                    IROp::EndPiecewise { default } => default,
+                    _ => unreachable!("loop above stops exactly at an EndPiecewise"),
+                };
+                let mut chosen = None;
+                for (comp, res) in arms {
+                    if *state.bools.get(&comp).ok_or(InterpError::UnboundId(comp))? {
+                        chosen = Some(res);
+                        break;
+                    }
+                }
+                let value = state.get(chosen.unwrap_or(default))?;
+                state.regs.insert(id, value);
+                i = end + 1;
+                continue;
+            }
+            IROp::InnerPiecewise { .. } | IROp::EndPiecewise { .. } => {
+                return Err(InterpError::UnmatchedPiecewise(id));
+            }
+            IROp::EndBroadcast { .. } => {
+                return Err(InterpError::UnmatchedBroadcast(id));
+            }
+            IROp::SetBroadcastArg(a, slot) => {
+                let idx = iter_idx.ok_or(InterpError::BroadcastArgOutsideLoop(id))?;
+                let elem = match state.get(a)? {
+                    Value::NumberList(v) => {
+                        Value::Number(*v.get(idx).ok_or(InterpError::IndexOutOfRange(idx))?)
+                    }
+                    Value::Vec2List(v) => {
+                        let (x, y) = *v.get(idx).ok_or(InterpError::IndexOutOfRange(idx))?;
+                        Value::Vec2(x, y)
+                    }
+                    Value::Vec3List(v) => {
+                        let (x, y, z) = *v.get(idx).ok_or(InterpError::IndexOutOfRange(idx))?;
+                        Value::Vec3(x, y, z)
+                    }
+                    other => {
+                        return Err(InterpError::TypeMismatch {
+                            expected: IRType::NumberList,
+                            found: other.type_of(),
+                        })
+                    }
+                };
+                state
+                    .broadcast_args
+                    .last_mut()
+                    .ok_or(InterpError::BroadcastArgOutsideLoop(id))?
+                    .insert(slot.id, elem);
+            }
+            IROp::LoadBroadcastArg(arg) => {
+                let value = state
+                    .broadcast_args
+                    .iter()
+                    .rev()
+                    .find_map(|scope| scope.get(&arg.id).cloned())
+                    .ok_or(InterpError::BroadcastArgOutsideLoop(id))?;
+                state.regs.insert(id, value);
+            }
+            IROp::Ret(a) => return Ok(Some(state.get(a)?)),
+            simple => step(id, simple, state)?,
+        }
+        i += 1;
+    }
+    Ok(None)
+}
+
+/// Evaluates an op with no control-flow of its own, writing its result into `state`.
+fn step(id: Id, op: IROp, state: &mut State) -> Result<(), InterpError> {
+    match op {
+        IROp::Binary(a, b, bop) => {
+            let x = state.num(a)?;
+            let y = state.num(b)?;
+            state.regs.insert(id, Value::Number(apply_binary(bop, x, y)?));
+        }
+        IROp::Unary(a, uop) => {
+            let x = state.num(a)?;
+            state.regs.insert(id, Value::Number(apply_unary(uop, x)?));
+        }
+        IROp::Const(c) => {
+            state.regs.insert(id, Value::Number(c));
+        }
+        IROp::IConst(c) => {
+            state.regs.insert(id, Value::Number(c as f64));
+        }
+        IROp::LoadArg(a) => {
+            let v = state
+                .args
+                .get(a.index() as usize)
+                .cloned()
+                .ok_or(InterpError::MissingArg(a.index()))?;
+            state.regs.insert(id, v);
+        }
+        IROp::CoordinateOf(a, access) => {
+            let r = match (state.get(a)?, access) {
+                (Value::Vec2(x, _), CoordinateAccess::DotAccessX) => x,
+                (Value::Vec2(_, y), CoordinateAccess::DotAccessY) => y,
+                (Value::Vec3(x, _, _), CoordinateAccess::DotAccessX) => x,
+                (Value::Vec3(_, y, _), CoordinateAccess::DotAccessY) => y,
+                (Value::Vec3(_, _, z), CoordinateAccess::DotAccessZ) => z,
+                (other, _) => {
+                    return Err(InterpError::TypeMismatch {
+                        expected: IRType::Vec2,
+                        found: other.type_of(),
+                    })
+                }
+            };
+            state.regs.insert(id, Value::Number(r));
+        }
+        IROp::Vec2(a, b) => {
+            let (x, y) = (state.num(a)?, state.num(b)?);
+            state.regs.insert(id, Value::Vec2(x, y));
+        }
+        IROp::Vec3(a, b, c) => {
+            let (x, y, z) = (state.num(a)?, state.num(b)?, state.num(c)?);
+            state.regs.insert(id, Value::Vec3(x, y, z));
+        }
+        IROp::NumberList(len) => {
+            let n = state.num(len)? as usize;
+            state.regs.insert(id, Value::NumberList(vec![0.0; n]));
+        }
+        IROp::Vec2List(len) => {
+            let n = state.num(len)? as usize;
+            state.regs.insert(id, Value::Vec2List(vec![(0.0, 0.0); n]));
+        }
+        IROp::Vec3List(len) => {
+            let n = state.num(len)? as usize;
+            state
+                .regs
+                .insert(id, Value::Vec3List(vec![(0.0, 0.0, 0.0); n]));
+        }
+        IROp::ListLength(a) => {
+            let n = match state.get(a)? {
+                Value::NumberList(v) => v.len(),
+                Value::Vec2List(v) => v.len(),
+                Value::Vec3List(v) => v.len(),
+                other => {
+                    return Err(InterpError::TypeMismatch {
+                        expected: IRType::NumberList,
+                        found: other.type_of(),
+                    })
+                }
+            };
+            state.regs.insert(id, Value::Number(n as f64));
+        }
+        IROp::Comparison { lhs, comp, rhs } => {
+            let (x, y) = (state.num(lhs)?, state.num(rhs)?);
+            state.bools.insert(id, apply_comparison(comp, x, y)?);
+        }
+        IROp::Ret(_)
+        | IROp::BeginBroadcast { .. }
+        | IROp::EndBroadcast { .. }
+        | IROp::SetBroadcastArg(_, _)
+        | IROp::LoadBroadcastArg(_)
+        | IROp::BeginPiecewise { .. }
+        | IROp::InnerPiecewise { .. }
+        | IROp::EndPiecewise { .. } => {
+            unreachable!("control-flow ops are dispatched by run() before reaching step()")
+        }
+    }
+    Ok(())
+}
+
+fn apply_binary(op: BinaryOp, a: f64, b: f64) -> Result<f64, InterpError> {
+    match op {
+        BinaryOp::Add => Ok(a + b),
+        BinaryOp::Sub => Ok(a - b),
+        BinaryOp::Mul => Ok(a * b),
+        BinaryOp::Div => Ok(a / b),
+        BinaryOp::Pow => Ok(a.powf(b)),
+        other => Err(InterpError::UnsupportedOp(format!("{other:?}"))),
+    }
+}
+
+fn apply_unary(op: UnaryOp, a: f64) -> Result<f64, InterpError> {
+    match op {
+        UnaryOp::Neg => Ok(-a),
+        other => Err(InterpError::UnsupportedOp(format!("{other:?}"))),
+    }
+}
+
+fn apply_comparison(comp: Comparison, a: f64, b: f64) -> Result<bool, InterpError> {
+    match comp {
+        Comparison::Equal => Ok(a == b),
+        Comparison::Less => Ok(a < b),
+        Comparison::LessEq => Ok(a <= b),
+        Comparison::Greater => Ok(a > b),
+        Comparison::GreaterEq => Ok(a >= b),
+        other => Err(InterpError::UnsupportedOp(format!("{other:?}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::ir::{ArgId, BroadcastArg, IRInstructionSeq};
+
+    #[test]
+    fn folds_binary_and_unary() {
+        let mut seq = IRInstructionSeq::default();
+        let a = seq.place(IROp::Const(2.0));
+        let b = seq.place(IROp::Const(3.0));
+        let sum = seq.place(IROp::Binary(a, b, BinaryOp::Add));
+        let negated = seq.place(IROp::Unary(sum, UnaryOp::Neg));
+        seq.place(IROp::Ret(negated));
+
+        assert_eq!(interpret(&seq, &[]), Ok(Value::Number(-5.0)));
+    }
+
+    #[test]
+    fn broadcasts_over_an_arg_list() {
+        let mut seq = IRInstructionSeq::default();
+        let arg_list = seq.place(IROp::LoadArg(ArgId::new(0, IRType::NumberList)));
+        let len = seq.place(IROp::ListLength(arg_list));
+        let one = seq.place(IROp::Const(1.0));
+        let last_idx = seq.place(IROp::Binary(len, one, BinaryOp::Sub));
+        let out_list = seq.place(IROp::NumberList(len));
+        let begin = seq.place(IROp::BeginBroadcast {
+            end_index: last_idx,
+            write_to: out_list,
+        });
+        let slot = BroadcastArg {
+            t: IRType::Number,
+            id: 0,
+        };
+        seq.place(IROp::SetBroadcastArg(arg_list, slot));
+        let elem = seq.place(IROp::LoadBroadcastArg(slot));
+        let two = seq.place(IROp::Const(2.0));
+        let doubled = seq.place(IROp::Binary(elem, two, BinaryOp::Mul));
+        seq.place(IROp::EndBroadcast {
+            begin,
+            ret: doubled,
+        });
+        seq.place(IROp::Ret(out_list));
+
+        let args = [Value::NumberList(vec![10.0, 20.0, 30.0])];
+        assert_eq!(
+            interpret(&seq, &args),
+            Ok(Value::NumberList(vec![20.0, 40.0, 60.0]))
+        );
+    }
+
+    #[test]
+    fn piecewise_falls_through_to_default() {
+        let mut seq = IRInstructionSeq::default();
+        let x = seq.place(IROp::Const(5.0));
+        let zero = seq.place(IROp::Const(0.0));
+        let cmp = seq.place(IROp::Comparison {
+            lhs: x,
+            comp: Comparison::Less,
+            rhs: zero,
+        });
+        let negated = seq.place(IROp::Unary(x, UnaryOp::Neg));
+        let begin = seq.place(IROp::BeginPiecewise {
+            comp: cmp,
+            res: negated,
+        });
+        seq.place(IROp::EndPiecewise { default: x });
+        seq.place(IROp::Ret(begin));
+
+        assert_eq!(interpret(&seq, &[]), Ok(Value::Number(5.0)));
+    }
+
+    #[test]
+    fn piecewise_selects_first_true_arm() {
+        let mut seq = IRInstructionSeq::default();
+        let x = seq.place(IROp::Const(-5.0));
+        let zero = seq.place(IROp::Const(0.0));
+        let is_negative = seq.place(IROp::Comparison {
+            lhs: x,
+            comp: Comparison::Less,
+            rhs: zero,
+        });
+        let negated = seq.place(IROp::Unary(x, UnaryOp::Neg));
+        let begin = seq.place(IROp::BeginPiecewise {
+            comp: is_negative,
+            res: negated,
+        });
+        let is_zero = seq.place(IROp::Comparison {
+            lhs: x,
+            comp: Comparison::Equal,
+            rhs: zero,
+        });
+        seq.place(IROp::InnerPiecewise {
+            comp: is_zero,
+            res: zero,
+        });
+        seq.place(IROp::EndPiecewise { default: x });
+        seq.place(IROp::Ret(begin));
+
+        assert_eq!(interpret(&seq, &[]), Ok(Value::Number(5.0)));
+    }
+}
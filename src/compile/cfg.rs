@@ -0,0 +1,257 @@
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
+
+use crate::compile::ir::{Id, IROp, IRInstructionSeq};
+
+/// Index of a [`BasicBlock`] within a [`BasicBlocks`] graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BlockIdx(usize);
+
+impl BlockIdx {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// A maximal straight-line run of instructions with no broadcast/piecewise boundary inside it.
+#[derive(Debug, Clone)]
+pub struct BasicBlock {
+    /// Instructions in this block, in `Id` order.
+    pub insts: Vec<(Id, IROp)>,
+    /// Blocks control can transfer to after this one finishes executing.
+    pub successors: Vec<BlockIdx>,
+}
+
+fn is_boundary(op: &IROp) -> bool {
+    matches!(
+        op,
+        IROp::BeginBroadcast { .. }
+            | IROp::EndBroadcast { .. }
+            | IROp::BeginPiecewise { .. }
+            | IROp::InnerPiecewise { .. }
+            | IROp::EndPiecewise { .. }
+    )
+}
+
+/// A basic-block view over an [`IRInstructionSeq`].
+///
+/// Control flow encoded as paired markers (`BeginBroadcast`/`EndBroadcast`,
+/// `BeginPiecewise`/`InnerPiecewise`/`EndPiecewise`) is split into blocks with explicit
+/// successor edges, so dataflow passes can walk the graph instead of re-scanning for the
+/// matching marker every time.
+#[derive(Debug)]
+pub struct BasicBlocks {
+    blocks: Vec<BasicBlock>,
+    entry: BlockIdx,
+    predecessors: OnceCell<Vec<Vec<BlockIdx>>>,
+}
+
+impl BasicBlocks {
+    /// Splits `seq` into basic blocks at broadcast/piecewise boundaries.
+    pub fn from_seq(seq: &IRInstructionSeq) -> Self {
+        let mut blocks: Vec<BasicBlock> = Vec::new();
+        // Maps a BeginBroadcast's Id to the index of the block it heads, so the matching
+        // EndBroadcast can find its loop body entry below.
+        let mut header_of: BTreeMap<Id, usize> = BTreeMap::new();
+        let mut current: Vec<(Id, IROp)> = Vec::new();
+
+        for (id, op) in seq.iter() {
+            if is_boundary(op) && !current.is_empty() {
+                blocks.push(BasicBlock {
+                    insts: std::mem::take(&mut current),
+                    successors: Vec::new(),
+                });
+            }
+            if matches!(op, IROp::BeginBroadcast { .. }) {
+                header_of.insert(*id, blocks.len());
+            }
+            current.push((*id, *op));
+        }
+        if !current.is_empty() {
+            blocks.push(BasicBlock {
+                insts: current,
+                successors: Vec::new(),
+            });
+        }
+
+        let len = blocks.len();
+        for i in 0..len {
+            if i + 1 < len {
+                blocks[i].successors.push(BlockIdx(i + 1));
+            }
+        }
+        // An EndBroadcast block additionally loops back to the header/body block that
+        // opened with its matching BeginBroadcast.
+        for i in 0..len {
+            if let Some((_, IROp::EndBroadcast { begin, .. })) = blocks[i].insts.first() {
+                if let Some(&header) = header_of.get(begin) {
+                    blocks[i].successors.insert(0, BlockIdx(header));
+                }
+            }
+        }
+
+        Self {
+            blocks,
+            entry: BlockIdx(0),
+            predecessors: OnceCell::new(),
+        }
+    }
+
+    pub fn entry(&self) -> BlockIdx {
+        self.entry
+    }
+
+    pub fn block(&self, idx: BlockIdx) -> &BasicBlock {
+        &self.blocks[idx.0]
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Predecessor edges, computed by inverting every block's successors the first time
+    /// this is called and cached for the lifetime of `self`.
+    pub fn predecessors(&self) -> &[Vec<BlockIdx>] {
+        self.predecessors.get_or_init(|| {
+            let mut preds = vec![Vec::new(); self.blocks.len()];
+            for (i, block) in self.blocks.iter().enumerate() {
+                for &succ in &block.successors {
+                    preds[succ.0].push(BlockIdx(i));
+                }
+            }
+            preds
+        })
+    }
+
+    /// Postorder DFS from the entry block: a node is emitted only once each of its
+    /// successors has been visited. A successor reached over a back edge is still on the
+    /// DFS stack at that point, so it is emitted after the node.
+    pub fn postorder(&self) -> Vec<BlockIdx> {
+        let mut visited = vec![false; self.blocks.len()];
+        let mut order = Vec::with_capacity(self.blocks.len());
+        if !self.blocks.is_empty() {
+            self.postorder_visit(self.entry, &mut visited, &mut order);
+        }
+        order
+    }
+
+    fn postorder_visit(&self, idx: BlockIdx, visited: &mut [bool], order: &mut Vec<BlockIdx>) {
+        if visited[idx.0] {
+            return;
+        }
+        visited[idx.0] = true;
+        for &succ in &self.blocks[idx.0].successors {
+            self.postorder_visit(succ, visited, order);
+        }
+        order.push(idx);
+    }
+
+    /// Reverse postorder: the natural visitation order for forward dataflow analyses.
+    pub fn reverse_postorder(&self) -> Vec<BlockIdx> {
+        let mut order = self.postorder();
+        order.reverse();
+        order
+    }
+
+    /// Whether the graph contains a back edge (i.e. a broadcast loop).
+    pub fn is_cyclic(&self) -> bool {
+        if self.blocks.is_empty() {
+            return false;
+        }
+        let mut visited = vec![false; self.blocks.len()];
+        let mut on_stack = vec![false; self.blocks.len()];
+        self.has_back_edge(self.entry, &mut visited, &mut on_stack)
+    }
+
+    fn has_back_edge(&self, idx: BlockIdx, visited: &mut [bool], on_stack: &mut [bool]) -> bool {
+        visited[idx.0] = true;
+        on_stack[idx.0] = true;
+        for &succ in &self.blocks[idx.0].successors {
+            if on_stack[succ.0] {
+                return true;
+            }
+            if !visited[succ.0] && self.has_back_edge(succ, visited, on_stack) {
+                return true;
+            }
+        }
+        on_stack[idx.0] = false;
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::ir::IRInstructionSeq;
+
+    /// Builds a broadcast with a 2-instruction body: `len`/`list` precede it, `tail`
+    /// follows it, matching the shape that exposed the off-by-one in the back-edge fix.
+    fn seq_with_multi_instruction_loop() -> (IRInstructionSeq, Id) {
+        let mut seq = IRInstructionSeq::default();
+        let len = seq.place(IROp::Const(3.0));
+        let list = seq.place(IROp::NumberList(len));
+        let begin = seq.place(IROp::BeginBroadcast {
+            end_index: len,
+            write_to: list,
+        });
+        seq.place(IROp::Const(1.0));
+        let last_body = seq.place(IROp::Const(2.0));
+        seq.place(IROp::EndBroadcast {
+            begin,
+            ret: last_body,
+        });
+        seq.place(IROp::Const(9.0));
+        (seq, begin)
+    }
+
+    #[test]
+    fn broadcast_back_edge_points_to_header_block_not_end_block() {
+        let (seq, _begin) = seq_with_multi_instruction_loop();
+        let cfg = BasicBlocks::from_seq(&seq);
+
+        assert_eq!(cfg.len(), 3);
+        // block 0: len, list. block 1: begin + 2-instruction body. block 2: end, tail.
+        assert_eq!(cfg.block(BlockIdx(0)).insts.len(), 2);
+        assert_eq!(cfg.block(BlockIdx(1)).insts.len(), 3);
+        assert_eq!(cfg.block(BlockIdx(2)).insts.len(), 2);
+
+        assert_eq!(cfg.block(BlockIdx(0)).successors, vec![BlockIdx(1)]);
+        assert_eq!(cfg.block(BlockIdx(1)).successors, vec![BlockIdx(2)]);
+        // The back edge must land on the header/body block (1), not the tail block (2).
+        assert_eq!(cfg.block(BlockIdx(2)).successors, vec![BlockIdx(1)]);
+    }
+
+    #[test]
+    fn predecessors_are_consistent_with_the_back_edge() {
+        let (seq, _begin) = seq_with_multi_instruction_loop();
+        let cfg = BasicBlocks::from_seq(&seq);
+
+        let preds = cfg.predecessors();
+        assert_eq!(preds[0], Vec::new());
+        assert_eq!(preds[1], vec![BlockIdx(0), BlockIdx(2)]);
+        assert_eq!(preds[2], vec![BlockIdx(1)]);
+    }
+
+    #[test]
+    fn broadcast_loop_is_cyclic() {
+        let (seq, _begin) = seq_with_multi_instruction_loop();
+        let cfg = BasicBlocks::from_seq(&seq);
+        assert!(cfg.is_cyclic());
+    }
+
+    #[test]
+    fn straight_line_sequence_is_acyclic() {
+        let mut seq = IRInstructionSeq::default();
+        let a = seq.place(IROp::Const(1.0));
+        seq.place(IROp::Unary(a, crate::ast::UnaryOp::Neg));
+
+        let cfg = BasicBlocks::from_seq(&seq);
+        assert_eq!(cfg.len(), 1);
+        assert!(!cfg.is_cyclic());
+        assert_eq!(cfg.reverse_postorder(), vec![BlockIdx(0)]);
+    }
+}
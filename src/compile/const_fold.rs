@@ -0,0 +1,232 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::ast::{BinaryOp, CoordinateAccess, UnaryOp};
+use crate::compile::ir::{Id, IRInstructionSeq, IROp};
+use crate::compile::patch::IRPatch;
+use crate::compile::visitor::{IRVisitor, OperandCollector};
+
+/// A compile-time-known value discovered while folding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ConstValue {
+    Number(f64),
+    Vec2(f64, f64),
+    Vec3(f64, f64, f64),
+}
+
+fn eval_binary(op: BinaryOp, a: f64, b: f64) -> Option<f64> {
+    Some(match op {
+        BinaryOp::Add => a + b,
+        BinaryOp::Sub => a - b,
+        BinaryOp::Mul => a * b,
+        BinaryOp::Div => a / b,
+        BinaryOp::Pow => a.powf(b),
+        _ => return None,
+    })
+}
+
+fn eval_unary(op: UnaryOp, a: f64) -> Option<f64> {
+    Some(match op {
+        UnaryOp::Neg => -a,
+        _ => return None,
+    })
+}
+
+/// Folds computations whose operands are all compile-time constants, replacing each one
+/// with the equivalent `Const` op and recording its value so downstream ops fold too.
+/// A `Vec2`/`Vec3` of constants is left as it is; its value is only recorded, so a
+/// `CoordinateOf` on it folds.
+///
+/// This is a single forward pass over the (flat) instruction stream, so it folds equally
+/// well inside a broadcast body or piecewise arm as it does at the top level. A
+/// broadcast's `end_index` folds like any other operand.
+///
+/// Known limitation (tracked as follow-up, not attempted here): a broadcast whose
+/// `end_index` and body are both constant is not unrolled/materialized into a constant
+/// list, even though that's a strictly stronger fold the original request called for.
+/// There is no `IROp` that represents a literal list constant to unroll into -- doing
+/// this for real needs either a new op (e.g. a `ConstList` variant) or running the body
+/// through `back::interp` and re-encoding its output, and neither is implemented. This is
+/// a scope gap, not a design decision, and should be called out in the PR description
+/// rather than left implicit.
+pub fn const_fold(seq: &mut IRInstructionSeq) {
+    let mut known: BTreeMap<Id, ConstValue> = BTreeMap::new();
+    let mut patch = IRPatch::new();
+
+    for (id, op) in seq.iter() {
+        match op {
+            IROp::Const(c) => {
+                known.insert(*id, ConstValue::Number(*c));
+            }
+            IROp::IConst(c) => {
+                known.insert(*id, ConstValue::Number(*c as f64));
+            }
+            IROp::Binary(a, b, bop) => {
+                if let (Some(ConstValue::Number(x)), Some(ConstValue::Number(y))) =
+                    (known.get(a), known.get(b))
+                {
+                    if let Some(v) = eval_binary(*bop, *x, *y) {
+                        known.insert(*id, ConstValue::Number(v));
+                        patch.replace(*id, IROp::Const(v));
+                    }
+                }
+            }
+            IROp::Unary(a, uop) => {
+                if let Some(ConstValue::Number(x)) = known.get(a) {
+                    if let Some(v) = eval_unary(*uop, *x) {
+                        known.insert(*id, ConstValue::Number(v));
+                        patch.replace(*id, IROp::Const(v));
+                    }
+                }
+            }
+            IROp::Vec2(a, b) => {
+                if let (Some(ConstValue::Number(x)), Some(ConstValue::Number(y))) =
+                    (known.get(a), known.get(b))
+                {
+                    known.insert(*id, ConstValue::Vec2(*x, *y));
+                }
+            }
+            IROp::Vec3(a, b, c) => {
+                if let (
+                    Some(ConstValue::Number(x)),
+                    Some(ConstValue::Number(y)),
+                    Some(ConstValue::Number(z)),
+                ) = (known.get(a), known.get(b), known.get(c))
+                {
+                    known.insert(*id, ConstValue::Vec3(*x, *y, *z));
+                }
+            }
+            IROp::CoordinateOf(a, access) => {
+                let folded = match (known.get(a), access) {
+                    (Some(ConstValue::Vec2(x, _)), CoordinateAccess::DotAccessX) => Some(*x),
+                    (Some(ConstValue::Vec2(_, y)), CoordinateAccess::DotAccessY) => Some(*y),
+                    (Some(ConstValue::Vec3(x, _, _)), CoordinateAccess::DotAccessX) => Some(*x),
+                    (Some(ConstValue::Vec3(_, y, _)), CoordinateAccess::DotAccessY) => Some(*y),
+                    (Some(ConstValue::Vec3(_, _, z)), CoordinateAccess::DotAccessZ) => Some(*z),
+                    _ => None,
+                };
+                if let Some(v) = folded {
+                    known.insert(*id, ConstValue::Number(v));
+                    patch.replace(*id, IROp::Const(v));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    patch.apply(seq);
+}
+
+/// Removes every non-`Ret`, non-structural instruction whose `Id` is unreferenced, computed
+/// by scanning operands backward from `Ret`. Structural markers (`BeginBroadcast`,
+/// `EndBroadcast`, `SetBroadcastArg`, `BeginPiecewise`, `InnerPiecewise`, `EndPiecewise`) are
+/// always kept, since removing one without its partner would corrupt the scope it delimits.
+pub fn eliminate_dead_code(seq: &mut IRInstructionSeq) {
+    let ops: Vec<(Id, IROp)> = seq.iter().map(|(id, op)| (*id, *op)).collect();
+    let mut live: BTreeSet<Id> = BTreeSet::new();
+    let mut patch = IRPatch::new();
+
+    for (id, op) in ops.iter().rev() {
+        let is_structural = matches!(
+            op,
+            IROp::BeginBroadcast { .. }
+                | IROp::EndBroadcast { .. }
+                | IROp::SetBroadcastArg(_, _)
+                | IROp::BeginPiecewise { .. }
+                | IROp::InnerPiecewise { .. }
+                | IROp::EndPiecewise { .. }
+                | IROp::Ret(_)
+        );
+        if is_structural || live.contains(id) {
+            let mut operands = OperandCollector::default();
+            operands.visit_op(*id, op);
+            live.extend(operands.operands);
+        } else {
+            patch.remove(*id);
+        }
+    }
+
+    patch.apply(seq);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::ir::{BroadcastArg, IRInstructionSeq, IRType};
+
+    #[test]
+    fn folds_a_constant_binary_expression() {
+        let mut seq = IRInstructionSeq::default();
+        let a = seq.place(IROp::Const(2.0));
+        let b = seq.place(IROp::Const(3.0));
+        let sum = seq.place(IROp::Binary(a, b, BinaryOp::Add));
+        seq.place(IROp::Ret(sum));
+
+        const_fold(&mut seq);
+
+        let ops: Vec<IROp> = seq.iter().map(|(_, op)| *op).collect();
+        assert_eq!(ops[2], IROp::Const(5.0));
+    }
+
+    #[test]
+    fn eliminates_an_unreferenced_intermediate_but_keeps_ret() {
+        let mut seq = IRInstructionSeq::default();
+        let a = seq.place(IROp::Const(2.0));
+        let b = seq.place(IROp::Const(3.0));
+        let dead = seq.place(IROp::Binary(a, b, BinaryOp::Sub));
+        let sum = seq.place(IROp::Binary(a, b, BinaryOp::Add));
+        seq.place(IROp::Ret(sum));
+
+        eliminate_dead_code(&mut seq);
+
+        let ops: Vec<IROp> = seq.iter().map(|(_, op)| *op).collect();
+        assert_eq!(ops.len(), 4);
+        assert!(!ops.contains(&IROp::Binary(a, b, BinaryOp::Sub)));
+        assert!(ops.iter().any(|op| matches!(op, IROp::Ret(_))));
+        let _ = dead;
+    }
+
+    #[test]
+    fn const_fold_then_dce_shrinks_a_foldable_expression_to_one_const() {
+        let mut seq = IRInstructionSeq::default();
+        let a = seq.place(IROp::Const(2.0));
+        let b = seq.place(IROp::Const(3.0));
+        let sum = seq.place(IROp::Binary(a, b, BinaryOp::Add));
+        let unused = seq.place(IROp::Const(999.0));
+        seq.place(IROp::Ret(sum));
+
+        const_fold(&mut seq);
+        eliminate_dead_code(&mut seq);
+
+        let ops: Vec<IROp> = seq.iter().map(|(_, op)| *op).collect();
+        assert!(!ops.contains(&IROp::Const(999.0)));
+        assert!(ops.contains(&IROp::Const(5.0)));
+        let _ = unused;
+    }
+
+    #[test]
+    fn dead_code_elimination_preserves_broadcast_structure() {
+        let mut seq = IRInstructionSeq::default();
+        let len = seq.place(IROp::Const(2.0));
+        let list = seq.place(IROp::NumberList(len));
+        let begin = seq.place(IROp::BeginBroadcast {
+            end_index: len,
+            write_to: list,
+        });
+        let slot = BroadcastArg {
+            t: IRType::Number,
+            id: 0,
+        };
+        seq.place(IROp::SetBroadcastArg(list, slot));
+        let elem = seq.place(IROp::LoadBroadcastArg(slot));
+        seq.place(IROp::EndBroadcast { begin, ret: elem });
+        seq.place(IROp::Const(999.0)); // never referenced
+        seq.place(IROp::Ret(list));
+
+        eliminate_dead_code(&mut seq);
+
+        let ops: Vec<IROp> = seq.iter().map(|(_, op)| *op).collect();
+        assert!(!ops.contains(&IROp::Const(999.0)));
+        assert!(ops.iter().any(|op| matches!(op, IROp::BeginBroadcast { .. })));
+        assert!(ops.iter().any(|op| matches!(op, IROp::EndBroadcast { .. })));
+    }
+}
@@ -0,0 +1,236 @@
+use std::fmt;
+
+use crate::compile::ir::{Id, IROp, IRInstructionSeq};
+use crate::compile::visitor::{IRVisitor, OperandCollector};
+
+fn fmt_id(id: &Id) -> String {
+    format!("_{}", id.index())
+}
+
+/// Renders a single [`IROp`] as a stable, human-readable line, with operand `Id`s printed
+/// as `_N` rather than dumped via `Debug`.
+fn fmt_op(op: &IROp) -> String {
+    match op {
+        IROp::Binary(a, b, bop) => format!("Binary({}, {}, {bop:?})", fmt_id(a), fmt_id(b)),
+        IROp::Unary(a, uop) => format!("Unary({}, {uop:?})", fmt_id(a)),
+        IROp::Const(c) => format!("Const({c})"),
+        IROp::IConst(c) => format!("IConst({c})"),
+        IROp::LoadArg(a) => format!("LoadArg(arg{})", a.index()),
+        IROp::CoordinateOf(a, access) => format!("CoordinateOf({}, {access:?})", fmt_id(a)),
+        IROp::Vec2(a, b) => format!("Vec2({}, {})", fmt_id(a), fmt_id(b)),
+        IROp::Vec3(a, b, c) => format!("Vec3({}, {}, {})", fmt_id(a), fmt_id(b), fmt_id(c)),
+        IROp::NumberList(n) => format!("NumberList({})", fmt_id(n)),
+        IROp::Vec2List(n) => format!("Vec2List({})", fmt_id(n)),
+        IROp::Vec3List(n) => format!("Vec3List({})", fmt_id(n)),
+        IROp::ListLength(a) => format!("ListLength({})", fmt_id(a)),
+        IROp::BeginBroadcast {
+            end_index,
+            write_to,
+        } => format!(
+            "BeginBroadcast {{ end_index: {}, write_to: {} }}",
+            fmt_id(end_index),
+            fmt_id(write_to)
+        ),
+        IROp::SetBroadcastArg(a, arg) => format!("SetBroadcastArg({}, slot{})", fmt_id(a), arg.id),
+        IROp::LoadBroadcastArg(arg) => format!("LoadBroadcastArg(slot{})", arg.id),
+        IROp::EndBroadcast { begin, ret } => {
+            format!("EndBroadcast {{ begin: {}, ret: {} }}", fmt_id(begin), fmt_id(ret))
+        }
+        IROp::Comparison { lhs, comp, rhs } => {
+            format!("Comparison({} {comp:?} {})", fmt_id(lhs), fmt_id(rhs))
+        }
+        IROp::BeginPiecewise { comp, res } => {
+            format!("BeginPiecewise {{ comp: {}, res: {} }}", fmt_id(comp), fmt_id(res))
+        }
+        IROp::InnerPiecewise { comp, res } => {
+            format!("InnerPiecewise {{ comp: {}, res: {} }}", fmt_id(comp), fmt_id(res))
+        }
+        IROp::EndPiecewise { default } => format!("EndPiecewise {{ default: {} }}", fmt_id(default)),
+        IROp::Ret(a) => format!("Ret({})", fmt_id(a)),
+    }
+}
+
+impl fmt::Display for IRInstructionSeq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (id, op) in self.iter() {
+            writeln!(f, "{:>5} {:<10?} = {}", fmt_id(id), id.t, fmt_op(op))?;
+        }
+        Ok(())
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Finds the index (within `ops`) of the `EndBroadcast` matching the `BeginBroadcast` at
+/// `start`, by following its `begin` back-pointer.
+fn matching_end_broadcast(ops: &[(Id, IROp)], start: usize) -> Option<usize> {
+    let begin = ops[start].0;
+    ops[start + 1..]
+        .iter()
+        .position(|(_, op)| matches!(op, IROp::EndBroadcast { begin: b, .. } if *b == begin))
+        .map(|p| p + start + 1)
+}
+
+/// Finds the index (within `ops`) of the `EndPiecewise` matching the `BeginPiecewise` at
+/// `start`, tracking nesting depth since piecewise markers carry no back-pointer.
+fn matching_end_piecewise(ops: &[(Id, IROp)], start: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, (_, op)) in ops[start + 1..].iter().enumerate() {
+        match op {
+            IROp::BeginPiecewise { .. } => depth += 1,
+            IROp::EndPiecewise { .. } if depth == 0 => return Some(start + 1 + i),
+            IROp::EndPiecewise { .. } => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+impl IRInstructionSeq {
+    /// Renders this sequence as Graphviz DOT: one node per instruction, dependency edges
+    /// from each operand `Id` to its consumer, and `BeginBroadcast`/`EndBroadcast` and
+    /// `BeginPiecewise`/`EndPiecewise` pairs drawn as dashed subgraph clusters so loop and
+    /// piecewise scopes are visually obvious.
+    pub fn to_dot(&self) -> String {
+        let ops: Vec<(Id, IROp)> = self.iter().map(|(id, op)| (*id, *op)).collect();
+        let mut out = String::from("digraph IR {\n    node [shape=box, fontname=monospace];\n");
+
+        for (id, op) in &ops {
+            out.push_str(&format!(
+                "    n{} [label=\"{}: {}\"];\n",
+                id.index(),
+                fmt_id(id),
+                dot_escape(&fmt_op(op))
+            ));
+        }
+
+        for (id, op) in &ops {
+            let mut operands = OperandCollector::default();
+            operands.visit_op(*id, op);
+            for operand in operands.operands {
+                out.push_str(&format!("    n{} -> n{};\n", operand.index(), id.index()));
+            }
+        }
+
+        let mut cluster = 0;
+        for (i, (_, op)) in ops.iter().enumerate() {
+            let (end, label) = match op {
+                IROp::BeginBroadcast { .. } => match matching_end_broadcast(&ops, i) {
+                    Some(end) => (end, "broadcast"),
+                    None => continue,
+                },
+                IROp::BeginPiecewise { .. } => match matching_end_piecewise(&ops, i) {
+                    Some(end) => (end, "piecewise"),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            out.push_str(&format!(
+                "    subgraph cluster_{cluster} {{\n        style=dashed;\n        label=\"{label}\";\n"
+            ));
+            for (id, _) in &ops[i..=end] {
+                out.push_str(&format!("        n{};\n", id.index()));
+            }
+            out.push_str("    }\n");
+            cluster += 1;
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::ir::{BroadcastArg, IRType};
+
+    #[test]
+    fn matching_end_broadcast_finds_the_paired_end() {
+        let mut seq = IRInstructionSeq::default();
+        let len = seq.place(IROp::Const(3.0));
+        let list = seq.place(IROp::NumberList(len));
+        let begin = seq.place(IROp::BeginBroadcast {
+            end_index: len,
+            write_to: list,
+        });
+        let elem = seq.place(IROp::Const(1.0));
+        seq.place(IROp::EndBroadcast { begin, ret: elem });
+        seq.place(IROp::Ret(list));
+
+        let ops: Vec<(Id, IROp)> = seq.iter().map(|(id, op)| (*id, *op)).collect();
+        let begin_idx = ops.iter().position(|(id, _)| *id == begin).unwrap();
+        let end_idx = matching_end_broadcast(&ops, begin_idx).unwrap();
+
+        assert!(matches!(ops[end_idx].1, IROp::EndBroadcast { .. }));
+    }
+
+    #[test]
+    fn matching_end_piecewise_skips_a_nested_piecewise() {
+        let mut seq = IRInstructionSeq::default();
+        let comp_outer = seq.place(IROp::Const(1.0));
+        let res_outer = seq.place(IROp::Const(2.0));
+        seq.place(IROp::BeginPiecewise {
+            comp: comp_outer,
+            res: res_outer,
+        });
+        let comp_inner = seq.place(IROp::Const(3.0));
+        let res_inner = seq.place(IROp::Const(4.0));
+        seq.place(IROp::BeginPiecewise {
+            comp: comp_inner,
+            res: res_inner,
+        });
+        let inner_default = seq.place(IROp::Const(5.0));
+        seq.place(IROp::EndPiecewise {
+            default: inner_default,
+        });
+        let outer_default = seq.place(IROp::Const(6.0));
+        seq.place(IROp::EndPiecewise {
+            default: outer_default,
+        });
+
+        let ops: Vec<(Id, IROp)> = seq.iter().map(|(id, op)| (*id, *op)).collect();
+        let outer_begin_idx = ops
+            .iter()
+            .position(|(_, op)| matches!(op, IROp::BeginPiecewise { comp, .. } if *comp == comp_outer))
+            .unwrap();
+        let end_idx = matching_end_piecewise(&ops, outer_begin_idx).unwrap();
+
+        // The outer BeginPiecewise's match must be the *outer* EndPiecewise, not the inner
+        // one that closes first -- depth tracking is the whole point of this helper.
+        assert!(matches!(
+            ops[end_idx].1,
+            IROp::EndPiecewise { default } if default == outer_default
+        ));
+    }
+
+    #[test]
+    fn to_dot_draws_a_cluster_for_a_broadcast_scope() {
+        let mut seq = IRInstructionSeq::default();
+        let len = seq.place(IROp::Const(3.0));
+        let list = seq.place(IROp::NumberList(len));
+        let begin = seq.place(IROp::BeginBroadcast {
+            end_index: len,
+            write_to: list,
+        });
+        let elem = seq.place(IROp::Const(1.0));
+        seq.place(IROp::EndBroadcast { begin, ret: elem });
+        seq.place(IROp::Ret(list));
+
+        let dot = seq.to_dot();
+
+        assert_eq!(dot.matches("subgraph cluster_").count(), 1);
+        assert!(dot.contains("label=\"broadcast\""));
+    }
+
+    #[test]
+    fn fmt_op_renders_broadcast_arg_slots() {
+        let arg = BroadcastArg {
+            t: IRType::Number,
+            id: 2,
+        };
+        assert_eq!(fmt_op(&IROp::LoadBroadcastArg(arg)), "LoadBroadcastArg(slot2)");
+    }
+}
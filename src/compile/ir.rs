@@ -62,6 +62,9 @@ impl Id {
     pub fn with_idx(&self, idx: u32) -> Self {
         Self { t: self.t, idx }
     }
+    pub fn index(&self) -> u32 {
+        self.idx
+    }
 }
 impl PartialEq for Id {
     fn eq(&self, other: &Self) -> bool {
@@ -90,6 +93,15 @@ pub struct BroadcastArg {
 // typed indentifier that identifies an item of type and index in args
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ArgId(Id);
+impl ArgId {
+    pub fn new(idx: u32, t: IRType) -> Self {
+        Self(Id::new(idx, t))
+    }
+    /// The index of this argument in the args list of the enclosing `IRChunk`.
+    pub fn index(&self) -> u32 {
+        self.0.idx
+    }
+}
 
 /// ### Desmoxide IR format
 /// This is mostly equivalent to the TAC-based IR format used by desmos (see https://github.com/DesModder/DesModder/blob/main/parsing/IR.ts).
@@ -196,7 +208,7 @@ impl IROp {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct IRInstructionSeq {
     backing: BTreeMap<Id, IROp>,
 }
@@ -246,6 +258,22 @@ impl IRInstructionSeq {
             .map(|a| a.1)
             .context("called latest on empty InstructionSeq")
     }
+    /// Iterates over every instruction in `Id` order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&Id, &IROp)> {
+        self.backing.iter()
+    }
+    /// Iterates over every instruction in `Id` order, yielding each op by mutable reference.
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = (&Id, &mut IROp)> {
+        self.backing.iter_mut()
+    }
+    /// Replaces the entire backing sequence with `ops`, assigning each a dense `Id` in order.
+    pub(crate) fn replace_all(&mut self, ops: Vec<IROp>) {
+        self.backing = ops
+            .into_iter()
+            .enumerate()
+            .map(|(i, op)| (Id::new(i as u32, op.type_of()), op))
+            .collect();
+    }
 }
 pub struct BroadcastBuilder<'a> {
     seq: &'a mut IRInstructionSeq,
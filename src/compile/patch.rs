@@ -0,0 +1,159 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::compile::ir::{Id, IRInstructionSeq, IROp, IRType};
+use crate::compile::visitor::{IRMutVisitor, IRVisitor, OperandCollector};
+
+/// Deferred edits to an [`IRInstructionSeq`], modeled on rustc's `MirPatch`.
+///
+/// `IRInstructionSeq::place` always appends at `last.idx + 1`, so there's no supported way
+/// to insert an instruction before an existing one, replace one, or remove one, without
+/// corrupting the dense `Id` invariant every other instruction relies on. An `IRPatch`
+/// instead accumulates edits and applies them in a single renumbering pass, rewriting every
+/// operand `Id` inside every `IROp` to its new index.
+#[derive(Default)]
+pub struct IRPatch {
+    inserts_before: BTreeMap<Id, Vec<IROp>>,
+    replacements: BTreeMap<Id, IROp>,
+    removals: BTreeSet<Id>,
+}
+
+impl IRPatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues `op` to be inserted immediately before `target` once [`apply`](Self::apply) runs.
+    pub fn add_op_before(&mut self, target: Id, op: IROp) {
+        self.inserts_before.entry(target).or_default().push(op);
+    }
+
+    /// Queues `target` to be replaced with `op` once [`apply`](Self::apply) runs.
+    pub fn replace(&mut self, target: Id, op: IROp) {
+        self.replacements.insert(target, op);
+    }
+
+    /// Queues `target` for removal once [`apply`](Self::apply) runs.
+    pub fn remove(&mut self, target: Id) {
+        self.removals.insert(target);
+    }
+
+    /// Applies every queued edit to `seq`: builds the new, dense instruction list (honoring
+    /// insertions, replacements, and removals in original `Id` order), then rewrites every
+    /// operand `Id` to its post-renumbering index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a surviving instruction still references a removed `Id` as an operand.
+    /// `remove()` is only safe to call on an `Id` with no remaining references; silently
+    /// leaving the stale `Id` in place would corrupt the IR in exactly the way this module
+    /// exists to prevent, so callers that get this wrong need a loud failure, not a quietly
+    /// broken sequence.
+    pub fn apply(self, seq: &mut IRInstructionSeq) {
+        let old_ops: Vec<(Id, IROp)> = seq.iter().map(|(id, op)| (*id, *op)).collect();
+
+        let mut new_ops: Vec<IROp> = Vec::new();
+        let mut remap: BTreeMap<Id, u32> = BTreeMap::new();
+
+        for (id, op) in old_ops {
+            if let Some(inserted) = self.inserts_before.get(&id) {
+                new_ops.extend(inserted.iter().copied());
+            }
+            if self.removals.contains(&id) {
+                continue;
+            }
+            let op = self.replacements.get(&id).copied().unwrap_or(op);
+            remap.insert(id, new_ops.len() as u32);
+            new_ops.push(op);
+        }
+
+        for op in &new_ops {
+            let mut operands = OperandCollector::default();
+            operands.visit_op(Id::new(0, IRType::Never), op);
+            for operand in &operands.operands {
+                assert!(
+                    !self.removals.contains(operand),
+                    "IRPatch::remove() deleted _{}, but a surviving instruction still \
+                     references it as an operand",
+                    operand.index()
+                );
+            }
+        }
+
+        let mut rewriter = OperandRewriter { remap: &remap };
+        for (i, op) in new_ops.iter_mut().enumerate() {
+            rewriter.visit_op_mut(Id::new(i as u32, IRType::Never), op);
+        }
+
+        seq.replace_all(new_ops);
+    }
+}
+
+/// Rewrites every operand `Id` it visits from its pre-patch index to its post-patch one.
+struct OperandRewriter<'a> {
+    remap: &'a BTreeMap<Id, u32>,
+}
+impl IRMutVisitor for OperandRewriter<'_> {
+    fn visit_operand_mut(&mut self, id: &mut Id) {
+        if let Some(&new_idx) = self.remap.get(id) {
+            *id = id.with_idx(new_idx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::BinaryOp;
+
+    #[test]
+    fn removes_an_unreferenced_instruction() {
+        let mut seq = IRInstructionSeq::default();
+        let a = seq.place(IROp::Const(1.0));
+        let dead = seq.place(IROp::Const(2.0));
+        seq.place(IROp::Ret(a));
+
+        let mut patch = IRPatch::new();
+        patch.remove(dead);
+        patch.apply(&mut seq);
+
+        let ops: Vec<IROp> = seq.iter().map(|(_, op)| *op).collect();
+        assert_eq!(ops.len(), 2);
+        assert!(ops.iter().any(|op| matches!(op, IROp::Ret(_))));
+    }
+
+    #[test]
+    fn replace_and_renumber_keep_downstream_operands_consistent() {
+        let mut seq = IRInstructionSeq::default();
+        let a = seq.place(IROp::Const(1.0));
+        let b = seq.place(IROp::Const(2.0));
+        let sum = seq.place(IROp::Binary(a, b, BinaryOp::Add));
+        seq.place(IROp::Ret(sum));
+
+        let mut patch = IRPatch::new();
+        patch.remove(a);
+        patch.remove(b);
+        patch.replace(sum, IROp::Const(3.0));
+        patch.apply(&mut seq);
+
+        let ops: Vec<(Id, IROp)> = seq.iter().map(|(id, op)| (*id, *op)).collect();
+        assert_eq!(ops.len(), 2);
+        assert_eq!(ops[0].1, IROp::Const(3.0));
+        assert_eq!(ops[1].1, IROp::Ret(ops[0].0));
+    }
+
+    #[test]
+    #[should_panic(expected = "still references it as an operand")]
+    fn removing_a_still_referenced_id_panics() {
+        let mut seq = IRInstructionSeq::default();
+        let a = seq.place(IROp::Const(1.0));
+        let b = seq.place(IROp::Const(2.0));
+        let sum = seq.place(IROp::Binary(a, b, BinaryOp::Add));
+        seq.place(IROp::Ret(sum));
+
+        let mut patch = IRPatch::new();
+        // `a` is still referenced by `sum`'s Binary operands below -- removing it without
+        // also removing or rewriting that reference must not silently corrupt the IR.
+        patch.remove(a);
+        patch.apply(&mut seq);
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+
+use crate::ast::CoordinateAccess;
+use crate::compile::ir::{Id, IRInstructionSeq, IROp, IRType};
+
+/// Validates `seq`'s type and scoping invariants, the way rustc validates MIR before
+/// codegen. Catching malformed IR here turns lowering bugs into actionable diagnostics
+/// instead of silently wrong output once it reaches a `back` target.
+pub fn validate(seq: &IRInstructionSeq) -> Result<()> {
+    let mut types: BTreeMap<Id, IRType> = BTreeMap::new();
+    let mut broadcast_scopes: Vec<Id> = Vec::new();
+    let mut piecewise_scopes: Vec<Id> = Vec::new();
+
+    for (id, op) in seq.iter() {
+        let get = |operand: &Id| -> Result<IRType> {
+            match types.get(operand) {
+                Some(IRType::Never) => {
+                    bail!(
+                        "instruction _{} uses _{}, which is Never-typed and may not be an operand",
+                        id.index(),
+                        operand.index()
+                    )
+                }
+                Some(t) => Ok(*t),
+                None => bail!(
+                    "instruction _{} references _{}, which has not been defined yet",
+                    id.index(),
+                    operand.index()
+                ),
+            }
+        };
+        let expect = |operand: &Id, expected: &[IRType]| -> Result<()> {
+            let ty = get(operand)?;
+            if !expected.contains(&ty) {
+                bail!(
+                    "instruction _{} expected operand _{} to have type in {:?}, found {:?}",
+                    id.index(),
+                    operand.index(),
+                    expected,
+                    ty
+                );
+            }
+            Ok(())
+        };
+        let list_types = [IRType::NumberList, IRType::Vec2List, IRType::Vec3List];
+
+        match op {
+            IROp::Binary(a, b, _) => {
+                expect(a, &[IRType::Number])?;
+                expect(b, &[IRType::Number])?;
+            }
+            IROp::Unary(a, _) => expect(a, &[IRType::Number])?,
+            IROp::Const(_) | IROp::IConst(_) | IROp::LoadArg(_) => {}
+            IROp::CoordinateOf(a, access) => {
+                let ty = get(a)?;
+                let valid = match (ty, access) {
+                    (IRType::Vec2, CoordinateAccess::DotAccessX | CoordinateAccess::DotAccessY) => {
+                        true
+                    }
+                    (IRType::Vec3, _) => true,
+                    _ => false,
+                };
+                if !valid {
+                    bail!(
+                        "instruction _{} accesses {:?} on operand _{} of type {:?}",
+                        id.index(),
+                        access,
+                        a.index(),
+                        ty
+                    );
+                }
+            }
+            IROp::Vec2(a, b) => {
+                expect(a, &[IRType::Number])?;
+                expect(b, &[IRType::Number])?;
+            }
+            IROp::Vec3(a, b, c) => {
+                expect(a, &[IRType::Number])?;
+                expect(b, &[IRType::Number])?;
+                expect(c, &[IRType::Number])?;
+            }
+            IROp::NumberList(len) | IROp::Vec2List(len) | IROp::Vec3List(len) => {
+                expect(len, &[IRType::Number])?;
+            }
+            IROp::ListLength(a) => expect(a, &list_types)?,
+            IROp::BeginBroadcast {
+                end_index,
+                write_to,
+            } => {
+                expect(end_index, &[IRType::Number])?;
+                expect(write_to, &list_types)?;
+                broadcast_scopes.push(*id);
+            }
+            IROp::SetBroadcastArg(a, _) => {
+                if broadcast_scopes.is_empty() {
+                    bail!(
+                        "SetBroadcastArg at _{} appears outside an open broadcast scope",
+                        id.index()
+                    );
+                }
+                expect(a, &list_types)?;
+            }
+            IROp::LoadBroadcastArg(_) => {
+                if broadcast_scopes.is_empty() {
+                    bail!(
+                        "LoadBroadcastArg at _{} appears outside an open broadcast scope",
+                        id.index()
+                    );
+                }
+            }
+            IROp::EndBroadcast { begin, ret } => {
+                expect(ret, &[IRType::Number, IRType::Vec2, IRType::Vec3])?;
+                match broadcast_scopes.pop() {
+                    Some(open) if open == *begin => {}
+                    Some(open) => bail!(
+                        "EndBroadcast at _{} points to _{} but the innermost open BeginBroadcast is _{}",
+                        id.index(),
+                        begin.index(),
+                        open.index()
+                    ),
+                    None => bail!("EndBroadcast at _{} has no matching BeginBroadcast", id.index()),
+                }
+            }
+            IROp::Comparison { lhs, rhs, .. } => {
+                expect(lhs, &[IRType::Number])?;
+                expect(rhs, &[IRType::Number])?;
+            }
+            IROp::BeginPiecewise { comp, res } => {
+                expect(comp, &[IRType::Bool])?;
+                expect(
+                    res,
+                    &[
+                        IRType::Number,
+                        IRType::Vec2,
+                        IRType::Vec3,
+                        IRType::NumberList,
+                        IRType::Vec2List,
+                        IRType::Vec3List,
+                    ],
+                )?;
+                piecewise_scopes.push(*id);
+            }
+            IROp::InnerPiecewise { comp, res } => {
+                if piecewise_scopes.is_empty() {
+                    bail!(
+                        "InnerPiecewise at _{} appears outside an open BeginPiecewise",
+                        id.index()
+                    );
+                }
+                expect(comp, &[IRType::Bool])?;
+                expect(
+                    res,
+                    &[
+                        IRType::Number,
+                        IRType::Vec2,
+                        IRType::Vec3,
+                        IRType::NumberList,
+                        IRType::Vec2List,
+                        IRType::Vec3List,
+                    ],
+                )?;
+            }
+            IROp::EndPiecewise { default } => {
+                if piecewise_scopes.pop().is_none() {
+                    bail!(
+                        "EndPiecewise at _{} has no matching BeginPiecewise",
+                        id.index()
+                    );
+                }
+                expect(
+                    default,
+                    &[
+                        IRType::Number,
+                        IRType::Vec2,
+                        IRType::Vec3,
+                        IRType::NumberList,
+                        IRType::Vec2List,
+                        IRType::Vec3List,
+                    ],
+                )?;
+            }
+            IROp::Ret(a) => expect(
+                a,
+                &[
+                    IRType::Number,
+                    IRType::Vec2,
+                    IRType::Vec3,
+                    IRType::NumberList,
+                    IRType::Vec2List,
+                    IRType::Vec3List,
+                ],
+            )?,
+        }
+
+        types.insert(*id, op.type_of());
+    }
+
+    if let Some(unclosed) = broadcast_scopes.first() {
+        bail!("BeginBroadcast at _{} has no matching EndBroadcast", unclosed.index());
+    }
+    if let Some(unclosed) = piecewise_scopes.first() {
+        bail!("BeginPiecewise at _{} has no matching EndPiecewise", unclosed.index());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::ir::{BroadcastArg, IRInstructionSeq};
+
+    #[test]
+    fn accepts_a_well_formed_sequence() {
+        let mut seq = IRInstructionSeq::default();
+        let a = seq.place(IROp::Const(1.0));
+        let b = seq.place(IROp::Const(2.0));
+        let sum = seq.place(IROp::Binary(a, b, crate::ast::BinaryOp::Add));
+        seq.place(IROp::Ret(sum));
+
+        assert!(validate(&seq).is_ok());
+    }
+
+    #[test]
+    fn accepts_a_well_formed_broadcast() {
+        let mut seq = IRInstructionSeq::default();
+        let len = seq.place(IROp::Const(3.0));
+        let list = seq.place(IROp::NumberList(len));
+        let begin = seq.place(IROp::BeginBroadcast {
+            end_index: len,
+            write_to: list,
+        });
+        let slot = BroadcastArg {
+            t: IRType::Number,
+            id: 0,
+        };
+        seq.place(IROp::SetBroadcastArg(list, slot));
+        let elem = seq.place(IROp::LoadBroadcastArg(slot));
+        seq.place(IROp::EndBroadcast { begin, ret: elem });
+        seq.place(IROp::Ret(list));
+
+        assert!(validate(&seq).is_ok());
+    }
+
+    #[test]
+    fn rejects_an_unmatched_end_broadcast() {
+        let mut seq = IRInstructionSeq::default();
+        let a = seq.place(IROp::Const(1.0));
+        // `a` is not even a BeginBroadcast, let alone an open one.
+        seq.place(IROp::EndBroadcast { begin: a, ret: a });
+
+        assert!(validate(&seq).is_err());
+    }
+
+    #[test]
+    fn rejects_a_stray_load_broadcast_arg() {
+        let mut seq = IRInstructionSeq::default();
+        seq.place(IROp::LoadBroadcastArg(BroadcastArg {
+            t: IRType::Number,
+            id: 0,
+        }));
+
+        assert!(validate(&seq).is_err());
+    }
+}
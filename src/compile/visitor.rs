@@ -0,0 +1,235 @@
+use crate::compile::ir::{ArgId, BroadcastArg, Id, IROp, IRInstructionSeq};
+
+/// Read-only traversal over an [`IRInstructionSeq`], analogous to rustc's MIR `Visitor`.
+///
+/// `visit_op`'s default implementation dispatches each [`IROp`] variant into the
+/// fine-grained hook below for every `Id` it references, so a pass author only needs to
+/// override the hooks relevant to their analysis instead of re-matching on `IROp`.
+pub trait IRVisitor {
+    fn visit_op(&mut self, _id: Id, op: &IROp) {
+        match op {
+            IROp::Binary(a, b, _) => {
+                self.visit_operand(a);
+                self.visit_operand(b);
+            }
+            IROp::Unary(a, _) => self.visit_operand(a),
+            IROp::Const(c) => self.visit_const(*c),
+            IROp::IConst(c) => self.visit_iconst(*c),
+            IROp::LoadArg(a) => self.visit_arg(a),
+            IROp::CoordinateOf(a, _) => self.visit_operand(a),
+            IROp::Vec2(a, b) => {
+                self.visit_operand(a);
+                self.visit_operand(b);
+            }
+            IROp::Vec3(a, b, c) => {
+                self.visit_operand(a);
+                self.visit_operand(b);
+                self.visit_operand(c);
+            }
+            IROp::NumberList(a) | IROp::Vec2List(a) | IROp::Vec3List(a) | IROp::ListLength(a) => {
+                self.visit_operand(a)
+            }
+            IROp::BeginBroadcast {
+                end_index,
+                write_to,
+            } => {
+                self.visit_operand(end_index);
+                self.visit_operand(write_to);
+            }
+            IROp::SetBroadcastArg(a, arg) => {
+                self.visit_operand(a);
+                self.visit_broadcast_arg(arg);
+            }
+            IROp::LoadBroadcastArg(arg) => self.visit_broadcast_arg(arg),
+            IROp::EndBroadcast { begin, ret } => {
+                self.visit_operand(begin);
+                self.visit_operand(ret);
+            }
+            IROp::Comparison { lhs, comp: _, rhs } => {
+                self.visit_operand(lhs);
+                self.visit_operand(rhs);
+            }
+            IROp::BeginPiecewise { comp, res } | IROp::InnerPiecewise { comp, res } => {
+                self.visit_operand(comp);
+                self.visit_operand(res);
+            }
+            IROp::EndPiecewise { default } => self.visit_operand(default),
+            IROp::Ret(a) => self.visit_operand(a),
+        }
+    }
+
+    /// Visits an operand `Id` referenced by the instruction currently being visited.
+    fn visit_operand(&mut self, _id: &Id) {}
+    /// Visits a floating point constant operand (`Const`).
+    fn visit_const(&mut self, _c: f64) {}
+    /// Visits an integer constant operand (`IConst`).
+    fn visit_iconst(&mut self, _c: i64) {}
+    /// Visits an argument reference (`LoadArg`).
+    fn visit_arg(&mut self, _arg: &ArgId) {}
+    /// Visits a broadcast argument slot (`SetBroadcastArg`/`LoadBroadcastArg`).
+    fn visit_broadcast_arg(&mut self, _arg: &BroadcastArg) {}
+}
+
+/// Mutable traversal over an [`IRInstructionSeq`], analogous to rustc's MIR `MutVisitor`.
+///
+/// Identical in shape to [`IRVisitor`], except every hook is handed a mutable reference so
+/// a pass can rewrite operands, constants, and broadcast arg slots in place.
+pub trait IRMutVisitor {
+    fn visit_op_mut(&mut self, _id: Id, op: &mut IROp) {
+        match op {
+            IROp::Binary(a, b, _) => {
+                self.visit_operand_mut(a);
+                self.visit_operand_mut(b);
+            }
+            IROp::Unary(a, _) => self.visit_operand_mut(a),
+            IROp::Const(c) => self.visit_const_mut(c),
+            IROp::IConst(c) => self.visit_iconst_mut(c),
+            IROp::LoadArg(a) => self.visit_arg_mut(a),
+            IROp::CoordinateOf(a, _) => self.visit_operand_mut(a),
+            IROp::Vec2(a, b) => {
+                self.visit_operand_mut(a);
+                self.visit_operand_mut(b);
+            }
+            IROp::Vec3(a, b, c) => {
+                self.visit_operand_mut(a);
+                self.visit_operand_mut(b);
+                self.visit_operand_mut(c);
+            }
+            IROp::NumberList(a) | IROp::Vec2List(a) | IROp::Vec3List(a) | IROp::ListLength(a) => {
+                self.visit_operand_mut(a)
+            }
+            IROp::BeginBroadcast {
+                end_index,
+                write_to,
+            } => {
+                self.visit_operand_mut(end_index);
+                self.visit_operand_mut(write_to);
+            }
+            IROp::SetBroadcastArg(a, arg) => {
+                self.visit_operand_mut(a);
+                self.visit_broadcast_arg_mut(arg);
+            }
+            IROp::LoadBroadcastArg(arg) => self.visit_broadcast_arg_mut(arg),
+            IROp::EndBroadcast { begin, ret } => {
+                self.visit_operand_mut(begin);
+                self.visit_operand_mut(ret);
+            }
+            IROp::Comparison { lhs, comp: _, rhs } => {
+                self.visit_operand_mut(lhs);
+                self.visit_operand_mut(rhs);
+            }
+            IROp::BeginPiecewise { comp, res } | IROp::InnerPiecewise { comp, res } => {
+                self.visit_operand_mut(comp);
+                self.visit_operand_mut(res);
+            }
+            IROp::EndPiecewise { default } => self.visit_operand_mut(default),
+            IROp::Ret(a) => self.visit_operand_mut(a),
+        }
+    }
+
+    /// Visits an operand `Id` referenced by the instruction currently being visited.
+    fn visit_operand_mut(&mut self, _id: &mut Id) {}
+    /// Visits a floating point constant operand (`Const`).
+    fn visit_const_mut(&mut self, _c: &mut f64) {}
+    /// Visits an integer constant operand (`IConst`).
+    fn visit_iconst_mut(&mut self, _c: &mut i64) {}
+    /// Visits an argument reference (`LoadArg`).
+    fn visit_arg_mut(&mut self, _arg: &mut ArgId) {}
+    /// Visits a broadcast argument slot (`SetBroadcastArg`/`LoadBroadcastArg`).
+    fn visit_broadcast_arg_mut(&mut self, _arg: &mut BroadcastArg) {}
+}
+
+/// Collects every operand `Id` an instruction references, via [`IRVisitor`].
+#[derive(Debug, Default)]
+pub struct OperandCollector {
+    pub operands: Vec<Id>,
+}
+impl IRVisitor for OperandCollector {
+    fn visit_operand(&mut self, id: &Id) {
+        self.operands.push(*id);
+    }
+}
+
+impl IRInstructionSeq {
+    /// Walks every instruction in `Id` order, invoking `visitor.visit_op` for each.
+    pub fn visit<V: IRVisitor>(&self, visitor: &mut V) {
+        for (id, op) in self.iter() {
+            visitor.visit_op(*id, op);
+        }
+    }
+    /// Walks every instruction in `Id` order, invoking `visitor.visit_op_mut` for each.
+    pub fn visit_mut<V: IRMutVisitor>(&mut self, visitor: &mut V) {
+        for (id, op) in self.iter_mut() {
+            visitor.visit_op_mut(*id, op);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::BinaryOp;
+    use crate::compile::ir::{IRInstructionSeq, IRType};
+
+    #[test]
+    fn operand_collector_gathers_every_operand_of_a_binary_op() {
+        let mut seq = IRInstructionSeq::default();
+        let a = seq.place(IROp::Const(1.0));
+        let b = seq.place(IROp::Const(2.0));
+        seq.place(IROp::Binary(a, b, BinaryOp::Add));
+
+        let mut collector = OperandCollector::default();
+        seq.visit(&mut collector);
+
+        assert_eq!(collector.operands, vec![a, b]);
+    }
+
+    #[test]
+    fn operand_collector_ignores_broadcast_arg_slots() {
+        let mut seq = IRInstructionSeq::default();
+        let len = seq.place(IROp::Const(3.0));
+        let list = seq.place(IROp::NumberList(len));
+        let begin = seq.place(IROp::BeginBroadcast {
+            end_index: len,
+            write_to: list,
+        });
+        let slot = BroadcastArg {
+            t: IRType::Number,
+            id: 0,
+        };
+        seq.place(IROp::SetBroadcastArg(list, slot));
+        let elem = seq.place(IROp::LoadBroadcastArg(slot));
+        seq.place(IROp::EndBroadcast { begin, ret: elem });
+
+        let mut collector = OperandCollector::default();
+        seq.visit(&mut collector);
+
+        // `LoadBroadcastArg` carries no operand `Id` at all (only a slot), so it must not
+        // appear among the collected operands even though it reads a value.
+        assert_eq!(
+            collector.operands,
+            vec![len, len, list, list, begin, elem]
+        );
+    }
+
+    /// A visitor that rewrites every `Const` to a fixed value, to exercise `IRMutVisitor`'s
+    /// dispatch alongside `IRVisitor`'s.
+    struct ConstZeroer;
+    impl IRMutVisitor for ConstZeroer {
+        fn visit_const_mut(&mut self, c: &mut f64) {
+            *c = 0.0;
+        }
+    }
+
+    #[test]
+    fn mut_visitor_rewrites_consts_in_place() {
+        let mut seq = IRInstructionSeq::default();
+        seq.place(IROp::Const(1.0));
+        seq.place(IROp::Const(2.0));
+
+        seq.visit_mut(&mut ConstZeroer);
+
+        let ops: Vec<IROp> = seq.iter().map(|(_, op)| *op).collect();
+        assert_eq!(ops, vec![IROp::Const(0.0), IROp::Const(0.0)]);
+    }
+}